@@ -0,0 +1,289 @@
+// Copyright 2015-2018 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::net::SocketAddr;
+
+use bytes::Bytes;
+use futures::sync::mpsc::{unbounded, UnboundedReceiver};
+use futures::{future, Async, Complete, Future, Poll, Stream};
+use h2::client::{self, SendRequest};
+use http::{Request, Uri};
+use openssl_lib::ssl::{SslConnector, SslMethod};
+use tokio::net::TcpStream;
+use tokio_openssl::SslConnectorExt;
+
+use openssl::tls_server::ParsedPkcs12;
+use proto::error::*;
+use proto::op::Message;
+use proto::serialize::binary::{BinDecodable, BinEncodable};
+use proto::xfer::{ignore_send, BasicDnsHandle, DnsHandle, DnsRequest, DnsResponse};
+
+const MIME_APPLICATION_DNS: &str = "application/dns-message";
+const DEFAULT_PATH: &str = "/dns-query";
+
+/// A `DnsHandle` for sending DNS queries as RFC 8484 DNS-over-HTTPS POST requests.
+#[derive(Clone)]
+pub struct HttpsClientStreamHandle {
+    inner: BasicDnsHandle<ProtoError>,
+}
+
+impl DnsHandle for HttpsClientStreamHandle {
+    type Error = ProtoError;
+
+    fn send<R: Into<DnsRequest>>(
+        &mut self,
+        request: R,
+    ) -> Box<Future<Item = DnsResponse, Error = Self::Error> + Send> {
+        self.inner.send(request)
+    }
+}
+
+/// Builder for a `DnsHandle`/background-future pair speaking DNS-over-HTTPS to a single
+/// remote endpoint.
+pub struct HttpsClientStreamBuilder {
+    name_server: SocketAddr,
+    dns_name: String,
+    path: String,
+    pkcs12: Option<ParsedPkcs12>,
+}
+
+impl HttpsClientStreamBuilder {
+    /// Creates a new builder for the given remote address and TLS server name.
+    ///
+    /// The query path defaults to `/dns-query`, per the RFC 8484 well-known default.
+    pub fn new(name_server: SocketAddr, dns_name: String) -> Self {
+        HttpsClientStreamBuilder {
+            name_server,
+            dns_name,
+            path: DEFAULT_PATH.to_string(),
+            pkcs12: None,
+        }
+    }
+
+    /// Overrides the HTTP path the DNS queries are POSTed to.
+    pub fn path(&mut self, path: String) -> &mut Self {
+        self.path = path;
+        self
+    }
+
+    /// Supplies a client certificate (already parsed via
+    /// `trust_dns_openssl::tls_server::read_cert`) to present for TLS client authentication.
+    pub fn pkcs12(&mut self, pkcs12: ParsedPkcs12) -> &mut Self {
+        self.pkcs12 = Some(pkcs12);
+        self
+    }
+
+    /// Returns a `HttpsClientStreamHandle` for issuing queries, and the `HttpsClientStream`
+    /// future that drives the underlying HTTP/2 connection; the latter must be spawned on an
+    /// executor for the former to make progress.
+    pub fn build(self) -> (HttpsClientStreamHandle, HttpsClientStream) {
+        let (message_sender, message_receiver) = unbounded();
+
+        let dns_name = self.dns_name;
+        let pkcs12 = self.pkcs12;
+        let path = self.path.clone();
+
+        let connect_dns_name = dns_name.clone();
+        let connect = TcpStream::connect(&self.name_server)
+            .map_err(|e| ProtoError::from(ProtoErrorKind::Msg(format!("tcp connect error: {}", e))))
+            .and_then(move |tcp_stream| {
+                let ssl_connector = SslConnector::builder(SslMethod::tls())
+                    .map_err(|e| ProtoError::from(ProtoErrorKind::Msg(format!("tls builder error: {}", e))))
+                    .and_then(|mut ssl_builder| {
+                        ssl_builder.set_alpn_protos(b"\x02h2").ok();
+
+                        if let Some(pkcs12) = pkcs12 {
+                            ssl_builder
+                                .set_private_key(&pkcs12.pkey)
+                                .and_then(|()| ssl_builder.set_certificate(&pkcs12.cert))
+                                .map_err(|e| {
+                                    ProtoError::from(ProtoErrorKind::Msg(format!(
+                                        "client cert/key rejected: {}",
+                                        e
+                                    )))
+                                })?;
+                        }
+
+                        Ok(ssl_builder.build())
+                    });
+
+                future::result(ssl_connector).and_then(move |ssl_connector| {
+                    ssl_connector
+                        .connect_async(&connect_dns_name, tcp_stream)
+                        .map_err(|e| {
+                            ProtoError::from(ProtoErrorKind::Msg(format!("tls handshake error: {}", e)))
+                        })
+                })
+            })
+            .and_then(|tls_stream| {
+                client::handshake(tls_stream).map_err(|e| {
+                    ProtoError::from(ProtoErrorKind::Msg(format!("h2 handshake error: {}", e)))
+                })
+            })
+            .and_then(|(send_request, connection)| {
+                // the h2 connection itself must be polled to drive I/O for every stream
+                // opened on `send_request`; run it to completion in the background.
+                tokio::spawn(connection.map_err(|_| ()));
+                Ok(send_request)
+            });
+
+        let stream = HttpsClientStream {
+            dns_name,
+            path,
+            state: HttpsClientStreamState::Connecting(Box::new(connect)),
+            message_receiver,
+        };
+
+        let handle = HttpsClientStreamHandle {
+            inner: BasicDnsHandle::new(message_sender),
+        };
+
+        (handle, stream)
+    }
+}
+
+enum HttpsClientStreamState {
+    Connecting(Box<Future<Item = SendRequest<Bytes>, Error = ProtoError> + Send>),
+    Connected(SendRequest<Bytes>),
+}
+
+/// The background future which owns the HTTP/2 connection to the DoH server.
+pub struct HttpsClientStream {
+    dns_name: String,
+    path: String,
+    state: HttpsClientStreamState,
+    message_receiver: UnboundedReceiver<(DnsRequest, Complete<Result<DnsResponse, ProtoError>>)>,
+}
+
+/// Builds the `https://{dns_name}{path}` URI every query is POSTed to.
+fn build_uri(dns_name: &str, path: &str) -> ProtoResult<Uri> {
+    format!("https://{}{}", dns_name, path)
+        .parse()
+        .map_err(|e| ProtoErrorKind::Msg(format!("invalid dns_name/path for URI: {}", e)).into())
+}
+
+impl HttpsClientStream {
+    fn uri(&self) -> ProtoResult<Uri> {
+        build_uri(&self.dns_name, &self.path)
+    }
+
+    /// Issues a single DNS request as one HTTP/2 stream, independent of any other
+    /// in-flight query, and completes `complete` once the response arrives.
+    fn dispatch(
+        &self,
+        send_request: &mut SendRequest<Bytes>,
+        request: DnsRequest,
+        complete: Complete<Result<DnsResponse, ProtoError>>,
+    ) {
+        let (message, options) = request.into_parts();
+
+        // each query gets exactly one POST/response exchange; multi-message exchanges (e.g.
+        // AXFR) aren't supported over this transport yet.
+        if options.expects_multiple_responses {
+            ignore_send(complete.send(Err(ProtoError::from(ProtoErrorKind::Msg(
+                "DNS-over-HTTPS does not support multi-response queries (e.g. AXFR)".to_string(),
+            )))));
+            return;
+        }
+
+        let result = message
+            .to_bytes()
+            .map_err(ProtoError::from)
+            .and_then(|message_bytes| self.uri().map(|uri| (uri, message_bytes)))
+            .and_then(|(uri, message_bytes)| {
+                let http_request = Request::post(uri)
+                    .header("content-type", MIME_APPLICATION_DNS)
+                    .header("accept", MIME_APPLICATION_DNS)
+                    .body(())
+                    .map_err(|e| ProtoError::from(ProtoErrorKind::Msg(format!("invalid http request: {}", e))))?;
+
+                let (response_future, mut send_stream) = send_request
+                    .send_request(http_request, false)
+                    .map_err(|e| ProtoError::from(ProtoErrorKind::Msg(format!("h2 send_request error: {}", e))))?;
+
+                send_stream
+                    .send_data(Bytes::from(message_bytes), true)
+                    .map_err(|e| ProtoError::from(ProtoErrorKind::Msg(format!("h2 send_data error: {}", e))))?;
+
+                Ok(response_future)
+            });
+
+        match result {
+            Ok(response_future) => {
+                let respond = response_future
+                    .map_err(|e| ProtoError::from(ProtoErrorKind::Msg(format!("h2 response error: {}", e))))
+                    .and_then(|response| {
+                        response
+                            .into_body()
+                            .concat2()
+                            .map_err(|e| ProtoError::from(ProtoErrorKind::Msg(format!("h2 body error: {}", e))))
+                    })
+                    .and_then(|body| Message::from_bytes(&body).map_err(ProtoError::from))
+                    .then(move |result| {
+                        ignore_send(complete.send(result.map(DnsResponse::from)));
+                        Ok(())
+                    });
+
+                tokio::spawn(respond);
+            }
+            Err(e) => {
+                ignore_send(complete.send(Err(e)));
+            }
+        }
+    }
+}
+
+impl Future for HttpsClientStream {
+    type Item = ();
+    type Error = ProtoError;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            let send_request = match self.state {
+                HttpsClientStreamState::Connecting(ref mut connecting) => {
+                    let send_request = try_ready!(connecting.poll());
+                    self.state = HttpsClientStreamState::Connected(send_request);
+                    continue;
+                }
+                HttpsClientStreamState::Connected(ref mut send_request) => send_request,
+            };
+
+            match try_ready!(
+                self.message_receiver
+                    .poll()
+                    .map_err(|()| ProtoError::from(ProtoErrorKind::Msg(
+                        "https message_receiver closed".to_string()
+                    )))
+            ) {
+                Some((request, complete)) => self.dispatch(send_request, request, complete),
+                None => return Ok(Async::Ready(())),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_uri_joins_dns_name_and_default_path() {
+        let uri = build_uri("dns.example.com", DEFAULT_PATH).unwrap();
+        assert_eq!(uri.to_string(), "https://dns.example.com/dns-query");
+    }
+
+    #[test]
+    fn build_uri_honors_overridden_path() {
+        let uri = build_uri("dns.example.com", "/custom-path").unwrap();
+        assert_eq!(uri.to_string(), "https://dns.example.com/custom-path");
+    }
+
+    #[test]
+    fn build_uri_rejects_invalid_dns_name() {
+        assert!(build_uri("[::1", DEFAULT_PATH).is_err());
+    }
+}
@@ -0,0 +1,28 @@
+// Copyright 2015-2018 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! DNS over HTTPS (RFC 8484) transport for the trust-dns client/resolver.
+//!
+//! This crate provides a `DnsHandle` implementation that tunnels DNS messages over an
+//! HTTP/2 connection, allowing queries to traverse networks/firewalls that only permit
+//! outbound HTTPS (port 443).
+
+extern crate bytes;
+extern crate futures;
+extern crate h2;
+extern crate http;
+extern crate openssl as openssl_lib;
+extern crate tokio;
+extern crate tokio_openssl;
+extern crate trust_dns_openssl as openssl;
+extern crate trust_dns_proto as proto;
+
+mod https_client_stream;
+
+pub use https_client_stream::{
+    HttpsClientStream, HttpsClientStreamBuilder, HttpsClientStreamHandle,
+};
@@ -19,8 +19,11 @@ use error::*;
 use op::{Message, MessageType, OpCode, Query};
 use xfer::{ignore_send, DnsRequest, DnsRequestOptions, DnsResponse};
 
-// TODO: this should be configurable
-const MAX_PAYLOAD_LEN: u16 = 1500 - 40 - 8; // 1500 (general MTU) - 40 (ipv6 header) - 8 (udp header)
+// Default advertised EDNS UDP payload size, per the DNS Flag Day 2020 recommendation: the
+// IPv6 minimum MTU of 1280 bytes minus a 40-byte IPv6 header and an 8-byte UDP header. This
+// avoids IP fragmentation on nearly all current network paths. Override per-request via
+// `DnsRequestOptions::edns_set_udp_payload_len`.
+const MAX_PAYLOAD_LEN: u16 = 1232;
 
 /// The StreamHandle is the general interface for communicating with the DnsFuture
 pub struct StreamHandle<E>
@@ -45,7 +48,7 @@ where
 }
 
 /// Implementations of Sinks for sending DNS messages
-pub trait DnsStreamHandle {
+pub trait DnsStreamHandle: Send {
     /// The Error type to be returned if there is an error
     type Error: FromProtoError;
 
@@ -55,7 +58,7 @@ pub trait DnsStreamHandle {
 
 impl<E> DnsStreamHandle for StreamHandle<E>
 where
-    E: FromProtoError,
+    E: FromProtoError + Send,
 {
     type Error = E;
 
@@ -85,14 +88,14 @@ impl<E: FromProtoError> BasicDnsHandle<E> {
 
 impl<E> DnsHandle for BasicDnsHandle<E>
 where
-    E: FromProtoError + 'static,
+    E: FromProtoError + Send + 'static,
 {
     type Error = E;
 
     fn send<R: Into<DnsRequest>>(
         &mut self,
         request: R,
-    ) -> Box<Future<Item = DnsResponse, Error = Self::Error>> {
+    ) -> Box<Future<Item = DnsResponse, Error = Self::Error> + Send> {
         let request = request.into();
         let (complete, receiver) = oneshot::channel();
         let message_sender: &mut _ = &mut self.message_sender;
@@ -141,7 +144,7 @@ pub trait DnsHandle: Clone {
     fn send<R: Into<DnsRequest>>(
         &mut self,
         request: R,
-    ) -> Box<Future<Item = DnsResponse, Error = Self::Error>>;
+    ) -> Box<Future<Item = DnsResponse, Error = Self::Error> + Send>;
 
     /// A *classic* DNS query
     ///
@@ -154,7 +157,7 @@ pub trait DnsHandle: Clone {
         &mut self,
         query: Query,
         options: DnsRequestOptions,
-    ) -> Box<Future<Item = DnsResponse, Error = Self::Error>> {
+    ) -> Box<Future<Item = DnsResponse, Error = Self::Error> + Send> {
         debug!("querying: {} {:?}", query.name(), query.query_type());
 
         // build the message
@@ -173,12 +176,71 @@ pub trait DnsHandle: Clone {
 
         // Extended dns
         {
-            // TODO: this should really be configurable...
             let edns = message.edns_mut();
-            edns.set_max_payload(MAX_PAYLOAD_LEN);
+            edns.set_max_payload(options.edns_set_udp_payload_len.unwrap_or(MAX_PAYLOAD_LEN));
             edns.set_version(0);
         }
 
         self.send(DnsRequest::new(message, options))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    fn assert_send<T: Send>() {}
+    fn assert_sync<T: Sync>() {}
+
+    #[test]
+    fn basic_dns_handle_is_send_and_sync() {
+        assert_send::<BasicDnsHandle<ProtoError>>();
+        assert_sync::<BasicDnsHandle<ProtoError>>();
+    }
+
+    #[test]
+    fn stream_handle_is_send_and_sync() {
+        assert_send::<StreamHandle<ProtoError>>();
+        assert_sync::<StreamHandle<ProtoError>>();
+    }
+
+    /// A `DnsHandle` that just records the last request it was asked to send, so `lookup`'s
+    /// message-building can be tested without an executor.
+    #[derive(Clone)]
+    struct RecordingHandle(Rc<RefCell<Option<DnsRequest>>>);
+
+    impl DnsHandle for RecordingHandle {
+        type Error = ProtoError;
+
+        fn send<R: Into<DnsRequest>>(
+            &mut self,
+            request: R,
+        ) -> Box<Future<Item = DnsResponse, Error = Self::Error> + Send> {
+            *self.0.borrow_mut() = Some(request.into());
+            Box::new(Err(ProtoError::from(ProtoErrorKind::Msg("unused".to_string()))).into_future())
+        }
+    }
+
+    #[test]
+    fn lookup_defaults_edns_payload_to_1232() {
+        let mut handle = RecordingHandle(Rc::new(RefCell::new(None)));
+        let _ = handle.lookup(Query::new(), DnsRequestOptions::default());
+
+        let request = handle.0.borrow_mut().take().expect("lookup did not call send");
+        assert_eq!(request.edns().expect("no edns set").max_payload(), 1232);
+    }
+
+    #[test]
+    fn lookup_honors_overridden_edns_payload() {
+        let mut handle = RecordingHandle(Rc::new(RefCell::new(None)));
+        let mut options = DnsRequestOptions::default();
+        options.edns_set_udp_payload_len = Some(4096);
+        let _ = handle.lookup(Query::new(), options);
+
+        let request = handle.0.borrow_mut().take().expect("lookup did not call send");
+        assert_eq!(request.edns().expect("no edns set").max_payload(), 4096);
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright 2015-2018 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Base trait for DNS transport implementations
+
+use std::ops::{Deref, DerefMut};
+
+pub mod dns_handle;
+
+pub use self::dns_handle::{BasicDnsHandle, DnsHandle, DnsStreamHandle, StreamHandle};
+use op::Message;
+
+/// Ignores the result of a send operation on a oneshot channel, this is because the receiver may have gone away
+pub fn ignore_send<T>(_: T) {}
+
+/// A EDNS Query request
+#[derive(Clone, Default, Debug)]
+pub struct DnsRequestOptions {
+    /// Whether this request allows for multiple responses (e.g. AXFR)
+    pub expects_multiple_responses: bool,
+
+    /// The UDP payload size to advertise via EDNS, overriding the default of 1232 bytes.
+    ///
+    /// Per the DNS Flag Day 2020 recommendation, 1232 bytes (the IPv6 minimum MTU of 1280
+    /// minus the 40-byte IPv6 header and 8-byte UDP header) avoids IP fragmentation on
+    /// nearly all current networks. Set this if the path is known to tolerate a larger
+    /// buffer, or to lower it further for particularly constrained links.
+    pub edns_set_udp_payload_len: Option<u16>,
+}
+
+/// A DNS request object
+///
+/// This wraps a `Message` and the options necessary to perform a request
+#[derive(Clone, Debug)]
+pub struct DnsRequest {
+    message: Message,
+    options: DnsRequestOptions,
+}
+
+impl DnsRequest {
+    /// Returns a new `DnsRequest` with the message and options
+    pub fn new(message: Message, options: DnsRequestOptions) -> Self {
+        DnsRequest { message, options }
+    }
+
+    /// Returns the options for this request
+    pub fn options(&self) -> &DnsRequestOptions {
+        &self.options
+    }
+
+    /// Consumes `self`, returning the wire message and the options used to build it
+    pub fn into_parts(self) -> (Message, DnsRequestOptions) {
+        (self.message, self.options)
+    }
+}
+
+impl Deref for DnsRequest {
+    type Target = Message;
+
+    fn deref(&self) -> &Self::Target {
+        &self.message
+    }
+}
+
+impl DerefMut for DnsRequest {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.message
+    }
+}
+
+impl From<Message> for DnsRequest {
+    fn from(message: Message) -> Self {
+        DnsRequest {
+            message,
+            options: DnsRequestOptions::default(),
+        }
+    }
+}
+
+/// A DNS response object
+///
+/// This wraps a `Message` received in response to a `DnsRequest`
+#[derive(Clone, Debug)]
+pub struct DnsResponse(Message);
+
+impl From<Message> for DnsResponse {
+    fn from(message: Message) -> Self {
+        DnsResponse(message)
+    }
+}
+
+impl Deref for DnsResponse {
+    type Target = Message;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,26 @@
+// Copyright 2015-2018 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! DNS over QUIC (RFC 9250) transport for the trust-dns client/resolver.
+//!
+//! Unlike the stream-oriented TCP/TLS transports, each DNS query here opens its own
+//! bidirectional QUIC stream, so queries are never blocked behind one another the way they
+//! can be on a single TCP connection (no head-of-line blocking across queries).
+
+extern crate byteorder;
+extern crate bytes;
+extern crate futures;
+extern crate quinn;
+extern crate tokio;
+extern crate trust_dns_proto as proto;
+
+mod quic_client_stream;
+
+pub use quic_client_stream::{QuicClientStream, QuicClientStreamHandle};
+
+/// The ALPN protocol identifier used to negotiate DNS-over-QUIC, per RFC 9250.
+pub const DOQ_ALPN: &[u8] = b"doq";
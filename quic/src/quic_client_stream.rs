@@ -0,0 +1,185 @@
+// Copyright 2015-2018 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::net::SocketAddr;
+
+use byteorder::{BigEndian, ByteOrder};
+use futures::Future;
+use quinn::{ClientConfigBuilder, Connection, Endpoint};
+use tokio::io::{read_exact, write_all};
+
+use proto::error::*;
+use proto::op::Message;
+use proto::serialize::binary::{BinDecodable, BinEncodable};
+use proto::xfer::{DnsHandle, DnsRequest, DnsResponse};
+
+use DOQ_ALPN;
+
+/// Returns the wildcard bind address matching `name_server`'s address family, so an IPv6
+/// `name_server` isn't handed a V4-only bound socket (which can never reach it).
+fn wildcard_bind_addr(name_server: &SocketAddr) -> SocketAddr {
+    if name_server.is_ipv6() {
+        "[::]:0".parse().unwrap()
+    } else {
+        "0.0.0.0:0".parse().unwrap()
+    }
+}
+
+/// Prefixes `message_bytes` with its big-endian 2-byte length, as DNS-over-QUIC requires.
+fn frame_message(message_bytes: &[u8]) -> Vec<u8> {
+    let mut framed = vec![0u8; 2 + message_bytes.len()];
+    BigEndian::write_u16(&mut framed[..2], message_bytes.len() as u16);
+    framed[2..].copy_from_slice(message_bytes);
+    framed
+}
+
+/// Connection parameters for a `QUIC` connection to `name_server`; `connect` turns them
+/// into a future yielding a usable `QuicClientStreamHandle`.
+pub struct QuicClientStream {
+    name_server: SocketAddr,
+    dns_name: String,
+}
+
+impl QuicClientStream {
+    /// Returns a new `QuicClientStream` whose `connect` method connects to `name_server`
+    /// (authenticated against `dns_name`) and yields a `QuicClientStreamHandle`.
+    pub fn new(name_server: SocketAddr, dns_name: String) -> Self {
+        QuicClientStream {
+            name_server,
+            dns_name,
+        }
+    }
+
+    /// Establishes the QUIC connection, negotiating the `doq` ALPN protocol identifier.
+    pub fn connect(self) -> Box<Future<Item = QuicClientStreamHandle, Error = ProtoError> + Send> {
+        let mut client_config = ClientConfigBuilder::default();
+        client_config.protocols(&[DOQ_ALPN]);
+
+        let mut endpoint = Endpoint::builder();
+        endpoint.default_client_config(client_config.build());
+
+        let dns_name = self.dns_name;
+        let bind_addr = wildcard_bind_addr(&self.name_server);
+        let (endpoint_driver, endpoint, _incoming) = match endpoint.bind(&bind_addr) {
+            Ok(bound) => bound,
+            Err(e) => {
+                return Box::new(futures::future::err(ProtoError::from(ProtoErrorKind::Msg(
+                    format!("failed to bind quic endpoint: {}", e),
+                ))))
+            }
+        };
+        tokio::spawn(endpoint_driver.map_err(|_| ()));
+
+        let connecting = match endpoint.connect(&self.name_server, &dns_name) {
+            Ok(connecting) => connecting,
+            Err(e) => {
+                return Box::new(futures::future::err(ProtoError::from(ProtoErrorKind::Msg(
+                    format!("invalid quic connect params: {}", e),
+                ))))
+            }
+        };
+
+        Box::new(
+            connecting
+                .map_err(|e| ProtoError::from(ProtoErrorKind::Msg(format!("quic connect error: {}", e))))
+                .map(|new_conn| QuicClientStreamHandle {
+                    connection: new_conn.connection,
+                }),
+        )
+    }
+}
+
+/// A `DnsHandle` which sends each query over its own bidirectional QUIC stream.
+#[derive(Clone)]
+pub struct QuicClientStreamHandle {
+    connection: Connection,
+}
+
+impl DnsHandle for QuicClientStreamHandle {
+    type Error = ProtoError;
+
+    fn send<R: Into<DnsRequest>>(
+        &mut self,
+        request: R,
+    ) -> Box<Future<Item = DnsResponse, Error = Self::Error> + Send> {
+        let (mut message, options) = request.into().into_parts();
+
+        // each query gets exactly one response on its own stream; multi-message exchanges
+        // (e.g. AXFR) aren't supported over this transport yet.
+        if options.expects_multiple_responses {
+            return Box::new(futures::future::err(ProtoError::from(ProtoErrorKind::Msg(
+                "DNS-over-QUIC does not support multi-response queries (e.g. AXFR)".to_string(),
+            ))));
+        }
+
+        // DNS-over-QUIC already gets confidentiality/integrity from the QUIC transport, so
+        // the message ID carries no security value; RFC 9250 recommends always sending 0,
+        // overriding whatever `DnsHandle::lookup`'s default ID-randomization produced.
+        message.set_id(0);
+
+        let bytes = match message.to_bytes() {
+            Ok(bytes) => bytes,
+            Err(e) => return Box::new(futures::future::err(ProtoError::from(e))),
+        };
+
+        let framed = frame_message(&bytes);
+
+        Box::new(
+            self.connection
+                .open_bi()
+                .map_err(|e| ProtoError::from(ProtoErrorKind::Msg(format!("quic open_bi error: {}", e))))
+                .and_then(move |(send, recv)| {
+                    write_all(send, framed)
+                        .map_err(|e| ProtoError::from(ProtoErrorKind::Msg(format!("quic send error: {}", e))))
+                        .and_then(move |(mut send, _)| {
+                            let _ = send.finish();
+                            read_exact(recv, [0u8; 2])
+                                .map_err(|e| ProtoError::from(ProtoErrorKind::Msg(format!("quic recv error: {}", e))))
+                        })
+                        .and_then(|(recv, len_buf)| {
+                            let response_len = BigEndian::read_u16(&len_buf) as usize;
+                            read_exact(recv, vec![0u8; response_len]).map_err(|e| {
+                                ProtoError::from(ProtoErrorKind::Msg(format!("quic recv error: {}", e)))
+                            })
+                        })
+                })
+                .and_then(|(_recv, response_bytes)| {
+                    Message::from_bytes(&response_bytes)
+                        .map(DnsResponse::from)
+                        .map_err(ProtoError::from)
+                }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_message_prepends_big_endian_length() {
+        let framed = frame_message(&[0xAB, 0xCD, 0xEF]);
+        assert_eq!(framed, vec![0x00, 0x03, 0xAB, 0xCD, 0xEF]);
+    }
+
+    #[test]
+    fn frame_message_handles_empty_input() {
+        assert_eq!(frame_message(&[]), vec![0x00, 0x00]);
+    }
+
+    #[test]
+    fn wildcard_bind_addr_matches_v4_name_server() {
+        let addr = wildcard_bind_addr(&"93.184.216.34:853".parse().unwrap());
+        assert_eq!(addr, "0.0.0.0:0".parse().unwrap());
+    }
+
+    #[test]
+    fn wildcard_bind_addr_matches_v6_name_server() {
+        let addr = wildcard_bind_addr(&"[2001:db8::1]:853".parse().unwrap());
+        assert_eq!(addr, "[::]:0".parse().unwrap());
+    }
+}
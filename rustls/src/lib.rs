@@ -0,0 +1,14 @@
+// Copyright 2015-2018 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A pure-Rust, rustls-based TLS acceptor, for use in place of `trust-dns-openssl` on
+//! platforms where linking openssl is undesirable or impractical. Enabled via the
+//! `dns-over-rustls` feature, as an alternative to `dns-over-openssl`.
+
+extern crate rustls;
+
+pub mod tls_server;
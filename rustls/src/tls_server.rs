@@ -0,0 +1,98 @@
+// Copyright 2015-2018 Benjamin Fry <benjaminfry@me.com>
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A rustls-based alternative to `trust_dns_openssl::tls_server::new_acceptor`, for servers
+//! that want DNS-over-TLS/HTTPS without linking openssl. Select this module with the
+//! `dns-over-rustls` feature in place of `dns-over-openssl`.
+
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+
+use rustls::internal::pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
+use rustls::{NoClientAuth, PrivateKey, ProtocolVersion, ServerConfig};
+
+pub use rustls::Certificate;
+
+/// Reads a PEM certificate chain and private key from disk.
+pub fn read_cert(
+    cert_path: &Path,
+    key_path: &Path,
+) -> Result<(Vec<Certificate>, PrivateKey), String> {
+    let cert_chain = {
+        let file = File::open(cert_path)
+            .map_err(|e| format!("error opening cert file: {:?}: {}", cert_path, e))?;
+        certs(&mut BufReader::new(file))
+            .map_err(|()| format!("badly formatted cert file: {:?}", cert_path))?
+    };
+
+    let key = {
+        let file = File::open(key_path)
+            .map_err(|e| format!("error opening key file: {:?}: {}", key_path, e))?;
+        let mut reader = BufReader::new(file);
+
+        // try PKCS8 first, then fall back to the legacy RSA PEM encoding
+        let keys = pkcs8_private_keys(&mut reader)
+            .map_err(|()| format!("badly formatted key file: {:?}", key_path))?;
+
+        if let Some(key) = keys.into_iter().next() {
+            key
+        } else {
+            let file = File::open(key_path)
+                .map_err(|e| format!("error opening key file: {:?}: {}", key_path, e))?;
+            rsa_private_keys(&mut BufReader::new(file))
+                .map_err(|()| format!("badly formatted key file: {:?}", key_path))?
+                .into_iter()
+                .next()
+                .ok_or_else(|| format!("no private key found in: {:?}", key_path))?
+        }
+    };
+
+    Ok((cert_chain, key))
+}
+
+/// Builds a `ServerConfig` for the given certificate chain and private key, restricted to
+/// TLS 1.2 and TLS 1.3, mirroring the `NO_SSLV2`/`NO_SSLV3`/`NO_TLSV1`/`NO_TLSV1_1` options
+/// the openssl acceptor sets.
+pub fn new_acceptor(
+    cert_chain: Vec<Certificate>,
+    key: PrivateKey,
+) -> io::Result<Arc<ServerConfig>> {
+    let mut config = ServerConfig::new(NoClientAuth::new());
+    config.versions = vec![ProtocolVersion::TLSv1_3, ProtocolVersion::TLSv1_2];
+
+    config
+        .set_single_cert(cert_chain, key)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{}", e)))?;
+
+    Ok(Arc::new(config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn testdata(name: &str) -> std::path::PathBuf {
+        Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/src/testdata")).join(name)
+    }
+
+    #[test]
+    fn read_cert_accepts_pkcs8_key() {
+        let (cert_chain, _key) =
+            read_cert(&testdata("test-cert.pem"), &testdata("test-key-pkcs8.pem")).unwrap();
+        assert_eq!(cert_chain.len(), 1);
+    }
+
+    #[test]
+    fn read_cert_falls_back_to_legacy_rsa_key() {
+        let (cert_chain, _key) =
+            read_cert(&testdata("test-cert.pem"), &testdata("test-key-rsa.pem")).unwrap();
+        assert_eq!(cert_chain.len(), 1);
+    }
+}